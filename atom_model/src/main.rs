@@ -1,9 +1,16 @@
 use bevy::prelude::*;
 use bevy::color::palettes::css::*;
+use bevy::asset::LoadState;
+use bevy::core_pipeline::bloom::{BloomCompositeMode, BloomSettings};
+use bevy::core_pipeline::tonemapping::Tonemapping;
+use bevy::core_pipeline::Skybox;
+use bevy::input::mouse::MouseMotion;
+use bevy::render::render_resource::{TextureViewDescriptor, TextureViewDimension};
+use bevy::window::CursorGrabMode;
 use bevy::{
     render::camera::Viewport, window::{PrimaryWindow, Window},
 };
-// theres no way to acc stop camera overlap in bevy but i can try to add a ui rectangle 
+// theres no way to acc stop camera overlap in bevy but i can try to add a ui rectangle
 
 #[derive(Component)]
 pub struct Grid {
@@ -18,17 +25,133 @@ struct FlyCamera {
     pitch: f32, // pitch is rotation around X axis in radians
 }
 
+#[derive(Resource, Default)]
+struct CursorGrabbed(bool);
+
+// speed/sensitivity knobs for CameraControllerPlugin, tunable at runtime or
+// by downstream users instead of being baked into fly_camera_controller
+#[derive(Resource)]
+struct MovementSettings {
+    sensitivity: f32,
+    speed: f32,
+}
+
+impl Default for MovementSettings {
+    fn default() -> Self {
+        Self {
+            sensitivity: 1.5,
+            speed: 5.0,
+        }
+    }
+}
+
+// remappable key assignments for CameraControllerPlugin
 #[derive(Resource)]
-struct OrbitAngle(f32);
+struct KeyBindings {
+    move_forward: KeyCode,
+    move_backward: KeyCode,
+    move_left: KeyCode,
+    move_right: KeyCode,
+    move_up: KeyCode,
+    move_down: KeyCode,
+    yaw_left: KeyCode,
+    yaw_right: KeyCode,
+    pitch_up: KeyCode,
+    pitch_down: KeyCode,
+    toggle_cursor_grab: KeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            move_forward: KeyCode::KeyW,
+            move_backward: KeyCode::KeyS,
+            move_left: KeyCode::KeyA,
+            move_right: KeyCode::KeyD,
+            move_up: KeyCode::KeyQ,
+            move_down: KeyCode::KeyE,
+            yaw_left: KeyCode::ArrowLeft,
+            yaw_right: KeyCode::ArrowRight,
+            pitch_up: KeyCode::ArrowUp,
+            pitch_down: KeyCode::ArrowDown,
+            toggle_cursor_grab: KeyCode::Escape,
+        }
+    }
+}
+
+// registers the fly-camera systems and their resources, so movement speed,
+// rotation sensitivity and key bindings can be tuned without editing the
+// systems themselves
+pub struct CameraControllerPlugin;
+
+impl Plugin for CameraControllerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MovementSettings>()
+            .init_resource::<KeyBindings>()
+            .init_resource::<CursorGrabbed>()
+            .add_systems(Update, fly_camera_controller)
+            .add_systems(Update, cursor_grab_toggle);
+    }
+}
+
+#[derive(Resource)]
+struct ActiveCameraIndex(usize);
+
+#[derive(Resource)]
+struct SkyboxConfig {
+    paths: Vec<&'static str>,
+    current: usize,
+}
+
+impl Default for SkyboxConfig {
+    fn default() -> Self {
+        Self {
+            paths: vec!["skyboxes/space.ktx2", "skyboxes/nebula.ktx2"],
+            current: 0,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct SkyboxState {
+    handle: Handle<Image>,
+    loaded: bool,
+}
+
 #[derive(Resource)]
-struct OrbitTilt(f32); // in radians
+struct BloomTuning {
+    intensity: f32,
+    threshold: f32,
+}
+
+impl Default for BloomTuning {
+    fn default() -> Self {
+        Self {
+            intensity: 0.3,
+            threshold: 0.6,
+        }
+    }
+}
 
 #[derive(Component)]
 struct Core;
 #[derive(Component)]
 struct Electron;
 
-#[derive(Resource, Default)]
+// one electron's shell: how far out it orbits, how fast, the normal of its
+// orbital plane (Vec3::Y = flat, anything else inclines the shell), and a
+// starting angle so electrons on the same shell don't overlap
+#[derive(Component)]
+struct Orbit {
+    radius: f32,
+    speed: f32,
+    tilt_axis: Vec3,
+    phase: f32,
+}
+
+// each electron fades its own trail independently rather than sharing one
+// global trace
+#[derive(Component, Default)]
 struct ElectronTrace {
     points: Vec<Vec3>,
     max_points: usize,
@@ -36,13 +159,16 @@ struct ElectronTrace {
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
+        .add_plugins(CameraControllerPlugin)
+        .init_resource::<BloomTuning>()
         .add_systems(Startup, setup)
         .add_systems(Update, grid)
-        .add_systems(Update, fly_camera_controller)
         .add_systems(Update, orbit_electron_system)
-        .add_systems(Update, orbit_tilt_control)
-        .add_systems(Update, electron_trace_gizmo_system) 
-        .add_systems(Update, setup_viewports)
+        .add_systems(Update, electron_trace_gizmo_system)
+        .add_systems(Update, camera_cycle_system)
+        .add_systems(Update, bloom_tuning_control)
+        .add_systems(Update, skybox_load_system)
+        .add_systems(Update, skybox_swap_control)
         .run();
 }
 
@@ -53,25 +179,82 @@ fn setup(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>
 ) {
-    // main camera
+    let skybox_config = SkyboxConfig::default();
+    let skybox_handle: Handle<Image> = asset_server.load(skybox_config.paths[skybox_config.current]);
+
+    // fixed vantage camera looking down the orbital plane
     commands.spawn((
-        Name::new("MainCamera"),
+        Name::new("OrbitalPlaneCamera"),
         Camera3dBundle {
+            camera: Camera {
+                hdr: true,
+                ..default()
+            },
+            tonemapping: Tonemapping::TonyMcMapface,
             ..default()
         },
-        Transform::from_xyz(5.0, 5.0, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
-        FlyCamera { yaw: 0.0, pitch: 0.0 },
+        BloomSettings {
+            composite_mode: BloomCompositeMode::Additive,
+            ..default()
+        },
+        Skybox {
+            image: skybox_handle.clone(),
+            brightness: 1000.0,
+        },
+        Transform::from_xyz(0.0, 8.0, 0.01).looking_at(Vec3::ZERO, Vec3::Y),
     ));
 
-    // game view camera
+    // fixed vantage camera looking edge-on at the orbital plane
     commands.spawn((
-        Name::new("GameViewCamera"),
+        Name::new("EdgeOnCamera"),
         Camera3dBundle {
+            camera: Camera {
+                hdr: true,
+                ..default()
+            },
+            tonemapping: Tonemapping::TonyMcMapface,
+            ..default()
+        },
+        BloomSettings {
+            composite_mode: BloomCompositeMode::Additive,
             ..default()
         },
+        Skybox {
+            image: skybox_handle.clone(),
+            brightness: 1000.0,
+        },
+        Transform::from_xyz(5.0, 0.0, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    // user-controlled free-look camera, always last in the cycle
+    commands.spawn((
+        Name::new("FreeCamera"),
+        Camera3dBundle {
+            camera: Camera {
+                hdr: true,
+                ..default()
+            },
+            tonemapping: Tonemapping::TonyMcMapface,
+            ..default()
+        },
+        BloomSettings {
+            composite_mode: BloomCompositeMode::Additive,
+            ..default()
+        },
+        Skybox {
+            image: skybox_handle.clone(),
+            brightness: 1000.0,
+        },
         Transform::from_xyz(5.0, 5.0, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+        FlyCamera { yaw: 0.0, pitch: 0.0 },
     ));
 
+    commands.insert_resource(SkyboxState {
+        handle: skybox_handle,
+        loaded: false,
+    });
+    commands.insert_resource(skybox_config);
+
     // light
     commands.spawn((
         PointLight {
@@ -100,78 +283,143 @@ fn setup(
         Core,
     ));
 
-    // electron
-    commands.spawn((
-        Name::new("Electron"),
-        Mesh3d(meshes.add(Sphere::new(0.2))),
-        MeshMaterial3d(materials.add(StandardMaterial {
-            base_color: Color::srgb_u8(255, 0, 0),
-            emissive: Color::srgb(0.4, 0.5, 1.0).into(),
-            ..default()
-        })),
-        Transform::from_xyz(2.0, 0.0, 0.0),
-        Electron,
-    ));
+    // electrons across a few shells, each with its own orbit and trace
+    let shells = [
+        Orbit { radius: 2.0, speed: 1.0, tilt_axis: Vec3::Y, phase: 0.0 },
+        Orbit { radius: 2.0, speed: 1.0, tilt_axis: Vec3::Y, phase: std::f32::consts::PI },
+        Orbit { radius: 3.2, speed: 0.6, tilt_axis: Vec3::new(0.4, 1.0, 0.0), phase: 0.0 },
+        Orbit { radius: 4.4, speed: 0.4, tilt_axis: Vec3::new(0.0, 1.0, 0.6), phase: std::f32::consts::FRAC_PI_2 },
+    ];
+    for (i, orbit) in shells.into_iter().enumerate() {
+        commands.spawn((
+            Name::new(format!("Electron{i}")),
+            Mesh3d(meshes.add(Sphere::new(0.2))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::srgb_u8(255, 0, 0),
+                emissive: Color::srgb(0.4, 0.5, 1.0).into(),
+                ..default()
+            })),
+            Transform::from_xyz(orbit.radius, 0.0, 0.0),
+            Electron,
+            orbit,
+            ElectronTrace {
+                points: Vec::new(),
+                max_points: 5000,
+            },
+        ));
+    }
 
-    commands.insert_resource(OrbitAngle(0.0));
-    commands.insert_resource(OrbitTilt(0.0)); // start with no tilt
-    commands.insert_resource(ElectronTrace {
-        points: Vec::new(),
-        max_points: 5000, 
-    });
+    commands.insert_resource(ActiveCameraIndex(0));
 }
 
-// update electron's position and store its trace
+// advance each electron along its own shell and store its own trace
 fn orbit_electron_system(
     time: Res<Time>,
-    mut angle: ResMut<OrbitAngle>,
-    tilt: Res<OrbitTilt>,
-    mut transform: Single<&mut Transform, With<Electron>>,
-    mut trace: ResMut<ElectronTrace>,
+    mut electrons: Query<(&Orbit, &mut Transform, &mut ElectronTrace), With<Electron>>,
 ) {
-    let radius = 2.0;
-    let speed = 1.0;
+    for (orbit, mut transform, mut trace) in &mut electrons {
+        let angle = orbit.phase + orbit.speed * time.elapsed_secs();
 
-    // advance orbit angle
-    angle.0 += speed * time.delta_secs();
+        let x = orbit.radius * angle.cos();
+        let z = orbit.radius * angle.sin();
+        let flat_pos = Vec3::new(x, 0.0, z);
 
-    let x = radius * angle.0.cos();
-    let z = radius * angle.0.sin();
-    let mut pos = Vec3::new(x, 0.0, z);
+        // rotate the flat orbit so its plane's normal matches tilt_axis
+        let tilt_quat = Quat::from_rotation_arc(Vec3::Y, orbit.tilt_axis.normalize());
+        let pos = tilt_quat * flat_pos;
 
-    // rotate orbit plane around Z axis by tilt.0
-    let tilt_quat = Quat::from_axis_angle(Vec3::Z, tilt.0);
-    pos = tilt_quat * pos;
+        trace.points.push(pos);
+        if trace.points.len() > trace.max_points {
+            trace.points.remove(0);
+        }
 
-    // store position in trace
-    trace.points.push(pos);
-    if trace.points.len() > trace.max_points {
-        trace.points.remove(0);
+        transform.translation = pos;
     }
+}
 
-    // update electron's transform
-    transform.translation = pos;
+fn electron_trace_gizmo_system(
+    mut gizmos: Gizmos,
+    traces: Query<&ElectronTrace>,
+) {
+    for trace in &traces {
+        let count = trace.points.len();
+        for (i, window) in trace.points.windows(2).enumerate() { // .windows returns an iterator over all contiguous windows of length size. The windows overlap.
+            let a = window[0];
+            let b = window[1];
+            // older segments are more transparent, so the trail fades out behind the electron
+            let alpha = (i + 1) as f32 / count as f32;
+            gizmos.line(a, b, WHITE.with_alpha(alpha)); // draw trace
+        }
+    }
 }
 
-fn orbit_tilt_control(
-    time: Res<Time>,
-    mut tilt: ResMut<OrbitTilt>,
+// lets users tune bloom intensity at runtime with the +/- keys
+fn bloom_tuning_control(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut tuning: ResMut<BloomTuning>,
+    mut bloom_settings: Query<&mut BloomSettings>,
 ) {
-    let tilt_amplitude = 1.0; // max tilt in radians (~57 degrees)
-    let tilt_speed = 0.1;     // how fast it oscillates
+    if keys.just_pressed(KeyCode::Equal) {
+        tuning.intensity = (tuning.intensity + 0.05).min(2.0);
+    }
+    if keys.just_pressed(KeyCode::Minus) {
+        tuning.intensity = (tuning.intensity - 0.05).max(0.0);
+    }
 
-    tilt.0 = tilt_amplitude * (time.elapsed_secs() * tilt_speed).sin();
+    for mut bloom in &mut bloom_settings {
+        bloom.intensity = tuning.intensity;
+        bloom.prefilter_settings.threshold = tuning.threshold;
+    }
 }
 
-fn electron_trace_gizmo_system(
-    mut gizmos: Gizmos,
-    trace: Res<ElectronTrace>,
+// once the cubemap image has finished loading as a single long strip, tell
+// the GPU to reinterpret it as a cube texture so Skybox can render it
+fn skybox_load_system(
+    asset_server: Res<AssetServer>,
+    mut skybox_state: ResMut<SkyboxState>,
+    mut images: ResMut<Assets<Image>>,
 ) {
-    for window in trace.points.windows(2) { // .windows returns an iterator over all contiguous windows of length size. The windows overlap.
-        let a = window[0];
-        let b = window[1];
-        gizmos.line(a, b, WHITE); // draw trace 
+    if skybox_state.loaded {
+        return;
+    }
+    if asset_server.load_state(&skybox_state.handle) != LoadState::Loaded {
+        return;
     }
+
+    let image = images.get_mut(&skybox_state.handle).unwrap();
+    if image.texture_descriptor.array_layer_count() == 1 {
+        image.reinterpret_stacked_2d_as_array(image.height() / image.width());
+        image.texture_view_descriptor = Some(TextureViewDescriptor {
+            dimension: Some(TextureViewDimension::Cube),
+            ..default()
+        });
+    }
+    skybox_state.loaded = true;
+}
+
+// swaps to the next configured cubemap on `V`
+fn skybox_swap_control(
+    keys: Res<ButtonInput<KeyCode>>,
+    asset_server: Res<AssetServer>,
+    mut config: ResMut<SkyboxConfig>,
+    mut skybox_state: ResMut<SkyboxState>,
+    mut skyboxes: Query<&mut Skybox>,
+) {
+    if !keys.just_pressed(KeyCode::KeyV) {
+        return;
+    }
+
+    config.current = (config.current + 1) % config.paths.len();
+    let handle: Handle<Image> = asset_server.load(config.paths[config.current]);
+
+    for mut skybox in &mut skyboxes {
+        skybox.image = handle.clone();
+    }
+
+    *skybox_state = SkyboxState {
+        handle,
+        loaded: false,
+    };
 }
 
 fn grid(
@@ -204,24 +452,38 @@ fn fly_camera_controller(
     mut query: Query<(&mut Transform, &mut FlyCamera)>,
     keys: Res<ButtonInput<KeyCode>>,
     time: Res<Time>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    cursor_grabbed: Res<CursorGrabbed>,
+    settings: Res<MovementSettings>,
+    bindings: Res<KeyBindings>,
 ) {
-    let speed = 5.0;
-    let rot_speed = 1.5; // radians/sec
+    // converts settings.sensitivity (felt as arrow-key radians/sec) into a
+    // per-pixel mouse-look scale that feels comparable
+    let mouse_sensitivity = settings.sensitivity * 0.00133;
+
+    // drain mouse deltas every frame; they are applied below only while the
+    // cursor is grabbed
+    let mouse_delta: Vec2 = mouse_motion.read().map(|ev| ev.delta).sum();
 
     for (mut transform, mut camera) in &mut query {
         // spin on Y axis
-        if keys.pressed(KeyCode::ArrowLeft) {
-            camera.yaw += rot_speed * time.delta_secs();
+        if keys.pressed(bindings.yaw_left) {
+            camera.yaw += settings.sensitivity * time.delta_secs();
         }
-        if keys.pressed(KeyCode::ArrowRight) {
-            camera.yaw -= rot_speed * time.delta_secs();
+        if keys.pressed(bindings.yaw_right) {
+            camera.yaw -= settings.sensitivity * time.delta_secs();
         }
         // pitch up/down
-        if keys.pressed(KeyCode::ArrowUp) {
-            camera.pitch += rot_speed * time.delta_secs();
+        if keys.pressed(bindings.pitch_up) {
+            camera.pitch += settings.sensitivity * time.delta_secs();
+        }
+        if keys.pressed(bindings.pitch_down) {
+            camera.pitch -= settings.sensitivity * time.delta_secs();
         }
-        if keys.pressed(KeyCode::ArrowDown) {
-            camera.pitch -= rot_speed * time.delta_secs();
+
+        if cursor_grabbed.0 {
+            camera.yaw -= mouse_delta.x * mouse_sensitivity;
+            camera.pitch -= mouse_delta.y * mouse_sensitivity;
         }
         camera.pitch = camera.pitch.clamp(-1.54, 1.54); // clamp pitch to avoid flipping
 
@@ -232,59 +494,95 @@ fn fly_camera_controller(
 
         // movement (WASD for horizontal, QE for vertical)
         let mut direction = Vec3::ZERO;
-        if keys.pressed(KeyCode::KeyW) {
+        if keys.pressed(bindings.move_forward) {
             direction += *transform.forward() * time.delta_secs();
         }
-        if keys.pressed(KeyCode::KeyS) {
+        if keys.pressed(bindings.move_backward) {
             direction -= *transform.forward() * time.delta_secs();
         }
-        if keys.pressed(KeyCode::KeyA) {
+        if keys.pressed(bindings.move_left) {
             direction -= *transform.right() * time.delta_secs();
         }
-        if keys.pressed(KeyCode::KeyD) {
+        if keys.pressed(bindings.move_right) {
             direction += *transform.right() * time.delta_secs();
         }
-        if keys.pressed(KeyCode::KeyQ) {
+        if keys.pressed(bindings.move_up) {
             direction += Vec3::Y * time.delta_secs();
         }
-        if keys.pressed(KeyCode::KeyE) {
+        if keys.pressed(bindings.move_down) {
             direction -= Vec3::Y * time.delta_secs();
         }
         if direction.length_squared() > 0.0 {
-            transform.translation += direction.normalize() * speed * time.delta_secs();
+            transform.translation += direction.normalize() * settings.speed * time.delta_secs();
         }
         println!("Camera Position: {:?}", transform.translation);
         println!("Camera Rotation: {:?}", transform.rotation);
     }
 }
 
-fn setup_viewports(
-    mut cameras: Query<(&Name, &mut Camera)>,
+fn cursor_grab_toggle(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut cursor_grabbed: ResMut<CursorGrabbed>,
+    bindings: Res<KeyBindings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if keys.just_pressed(bindings.toggle_cursor_grab) {
+        cursor_grabbed.0 = !cursor_grabbed.0;
+
+        let mut window = windows.single_mut();
+        if cursor_grabbed.0 {
+            window.cursor.grab_mode = CursorGrabMode::Locked;
+            window.cursor.visible = false;
+        } else {
+            window.cursor.grab_mode = CursorGrabMode::None;
+            window.cursor.visible = true;
+        }
+    }
+}
+
+// cycles through all spawned cameras on `C`, always keeping the free-look
+// camera as the last stop in the cycle, and gives the active one the full
+// window viewport
+fn camera_cycle_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut index: ResMut<ActiveCameraIndex>,
+    mut cameras: Query<(Entity, &Name, &mut Camera)>,
     windows: Query<&Window, With<PrimaryWindow>>,
 ) {
-    let window = windows.single();
-    let width = window.resolution.physical_width();
-    let height = window.resolution.physical_height();
-
-    // Size of the small camera (e.g., 1/3 of window width and height)
-    let small_width = width / 3;
-    let small_height = height / 3;
-
-    for (name, mut camera) in &mut cameras {
-        if name.as_str() == "MainCamera" {
-            // Main camera covers the whole window
-            camera.viewport = Some(Viewport {
-                physical_position: UVec2::new(0, 0),
-                physical_size: UVec2::new(width, height),
-                ..default()
-            });
-        } else if name.as_str() == "GameViewCamera" {
-            // GameViewCamera is a small rectangle in the bottom-right corner
-            camera.viewport = Some(Viewport {
-                physical_position: UVec2::new(width - small_width, 0), // bottom-right
-                physical_size: UVec2::new(small_width, small_height),
-                ..default()
-            });
+    let mut ordered: Vec<Entity> = Vec::new();
+    let mut free_camera = None;
+    for (entity, name, _) in &cameras {
+        if name.as_str() == "FreeCamera" {
+            free_camera = Some(entity);
+        } else {
+            ordered.push(entity);
         }
     }
+    ordered.extend(free_camera);
+
+    if ordered.is_empty() {
+        return;
+    }
+    index.0 %= ordered.len();
+
+    if keys.just_pressed(KeyCode::KeyC) {
+        index.0 = (index.0 + 1) % ordered.len();
+    }
+    let active = ordered[index.0];
+
+    let window = windows.single();
+    let full_viewport = Viewport {
+        physical_position: UVec2::ZERO,
+        physical_size: UVec2::new(
+            window.resolution.physical_width(),
+            window.resolution.physical_height(),
+        ),
+        ..default()
+    };
+
+    for (entity, _, mut camera) in &mut cameras {
+        let is_active = entity == active;
+        camera.is_active = is_active;
+        camera.viewport = is_active.then(|| full_viewport.clone());
+    }
 }